@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
@@ -15,45 +15,166 @@ use kaspa_consensus_core::{
     constants::TX_VERSION,
     sign::sign,
     subnets::SUBNETWORK_ID_NATIVE,
-    tx::{MutableTransaction, Transaction, TransactionInput, TransactionOutpoint, TransactionOutput, UtxoEntry},
+    tx::{MutableTransaction, Transaction, TransactionId, TransactionInput, TransactionOutpoint, TransactionOutput, UtxoEntry},
 };
 use kaspa_core::{info, kaspad_env::version, time::unix_now, warn};
 use kaspa_grpc_client::GrpcClient;
-use kaspa_rpc_core::{api::rpc::RpcApi, notify::mode::NotificationMode};
+use kaspa_rpc_core::{
+    api::rpc::RpcApi,
+    notify::{connection::ChannelConnection, mode::NotificationMode},
+    Notification, Scope, UtxosChangedScope, VirtualChainChangedScope,
+};
 use kaspa_txscript::pay_to_address_script;
 use parking_lot::RwLock;
 use rayon::prelude::*;
-use secp256k1::{rand::thread_rng, KeyPair};
+use secp256k1::{
+    rand::{rngs::StdRng, thread_rng, RngCore, SeedableRng},
+    KeyPair,
+};
+use sha2::{Digest, Sha256};
 use tokio::{
-    sync::mpsc,
-    time::{interval, MissedTickBehavior},
+    sync::{mpsc, Mutex as AsyncMutex},
+    time::{interval, Interval, MissedTickBehavior},
 };
+use workflow_core::channel::Channel;
 
 const DEFAULT_SEND_AMOUNT: u64 = 10_000;
 
-const FEE_PER_MASS: u64 = 10;
+const MAX_UTXOS: usize = 84;
+
+/// Target balance handed to each sub-account by the initial funding split: enough for many
+/// iterations of the default send amount before that account needs to refresh from the node.
+const ACCOUNT_FUNDING_AMOUNT: u64 = DEFAULT_SEND_AMOUNT * 10_000;
+
+/// Mempool occupancy at/under which the adaptive fee controller uses the base `--fee-rate`.
+const MEMPOOL_LOW_WATER_MARK: u64 = 2_000;
+
+/// Mempool occupancy at/above which the adaptive fee controller ramps all the way up to
+/// `--max-fee-rate`. This doubles as the "mempool full" threshold that pauses submission.
+const MEMPOOL_HIGH_WATER_MARK: u64 = 10_000;
+
+/// Number of exponentially-spaced latency buckets: bucket `i` covers `[2^i, 2^(i+1))` milliseconds,
+/// so `LATENCY_NUM_BUCKETS` of 24 caps out at ~4.6 hours, far beyond any tx we'd still wait on.
+const LATENCY_NUM_BUCKETS: usize = 24;
+
+/// Txs that haven't confirmed within this long are assumed evicted from the mempool and are
+/// dropped from the pending-latency map so it doesn't grow unbounded.
+const LATENCY_TIMEOUT_MS: u64 = 10 * 60 * 1000;
 
+/// Throughput counters shared across every account's `submit_loop`, so a multi-account run reports
+/// one combined rate instead of one line per account.
 struct Stats {
-    num_txs: usize,
-    num_utxos: usize,
-    utxos_amount: u64,
-    num_outs: usize,
-    since: u64,
+    num_txs: AtomicUsize,
+    num_utxos: AtomicUsize,
+    utxos_amount: AtomicU64,
+    num_outs: AtomicUsize,
+}
+
+impl Stats {
+    fn new() -> Self {
+        Self { num_txs: AtomicUsize::new(0), num_utxos: AtomicUsize::new(0), utxos_amount: AtomicU64::new(0), num_outs: AtomicUsize::new(0) }
+    }
+
+    fn record(&self, num_utxos: usize, utxos_amount: u64, num_outs: usize) {
+        self.num_txs.fetch_add(1, Ordering::Relaxed);
+        self.num_utxos.fetch_add(num_utxos, Ordering::Relaxed);
+        self.utxos_amount.fetch_add(utxos_amount, Ordering::Relaxed);
+        self.num_outs.fetch_add(num_outs, Ordering::Relaxed);
+    }
+
+    /// Drains the current window and returns `(num_txs, num_utxos, utxos_amount, num_outs)`.
+    fn take(&self) -> (usize, usize, u64, usize) {
+        (
+            self.num_txs.swap(0, Ordering::Relaxed),
+            self.num_utxos.swap(0, Ordering::Relaxed),
+            self.utxos_amount.swap(0, Ordering::Relaxed),
+            self.num_outs.swap(0, Ordering::Relaxed),
+        )
+    }
+}
+
+/// Lock-free, fixed-bucket histogram of confirmation latencies, read out as p50/p90/p99 on each
+/// reporting interval alongside the throughput stats.
+struct LatencyHistogram {
+    buckets: [AtomicUsize; LATENCY_NUM_BUCKETS],
+    timed_out: AtomicUsize,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self { buckets: std::array::from_fn(|_| AtomicUsize::new(0)), timed_out: AtomicUsize::new(0) }
+    }
+
+    fn record(&self, latency_ms: u64) {
+        let bucket = (64 - latency_ms.max(1).leading_zeros() as usize - 1).min(LATENCY_NUM_BUCKETS - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_timeout(&self) {
+        self.timed_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drains the current window and returns `(p50, p90, p99, timed_out)`: each percentile is the
+    /// lower bound of its bucket in milliseconds, and `timed_out` is the number of timed-out txs.
+    fn take_percentiles(&self) -> (u64, u64, u64, usize) {
+        let counts: Vec<usize> = self.buckets.iter().map(|b| b.swap(0, Ordering::Relaxed)).collect();
+        let timed_out = self.timed_out.swap(0, Ordering::Relaxed);
+        let total: usize = counts.iter().sum();
+        if total == 0 {
+            return (0, 0, 0, timed_out);
+        }
+
+        let targets = [0.50, 0.90, 0.99];
+        let mut percentiles = [0u64; 3];
+        let mut target_idx = 0;
+        let mut cumulative = 0usize;
+        for (i, count) in counts.iter().enumerate() {
+            cumulative += count;
+            while target_idx < targets.len() && cumulative as f64 >= targets[target_idx] * total as f64 {
+                percentiles[target_idx] = 1u64 << i;
+                target_idx += 1;
+            }
+        }
+        (percentiles[0], percentiles[1], percentiles[2], timed_out)
+    }
 }
 
 pub struct Args {
     pub private_key: Option<String>,
     pub tps: u64,
     pub rpc_server: String,
+    pub sweep_to: Option<String>,
+    pub fee_rate: u64,
+    pub max_fee_rate: u64,
+    pub payload_size: usize,
+    pub payload_seed: Option<u64>,
+    pub accounts: usize,
 }
 
 impl Args {
     fn parse() -> Self {
         let m = cli().get_matches();
+        let sweep_to = m.subcommand_matches("sweep").map(|sm| sm.get_one::<String>("to").cloned().unwrap());
+        let fee_rate = m.get_one::<u64>("fee-rate").cloned().unwrap();
+        let max_fee_rate = m.get_one::<u64>("max-fee-rate").cloned().unwrap();
+        if max_fee_rate < fee_rate {
+            cli()
+                .error(
+                    clap::error::ErrorKind::ValueValidation,
+                    format!("--max-fee-rate ({max_fee_rate}) must be greater than or equal to --fee-rate ({fee_rate})"),
+                )
+                .exit();
+        }
         Args {
             private_key: m.get_one::<String>("private-key").cloned(),
             tps: m.get_one::<u64>("tps").cloned().unwrap(),
             rpc_server: m.get_one::<String>("rpcserver").cloned().unwrap_or("localhost:16210".to_owned()),
+            sweep_to,
+            fee_rate,
+            max_fee_rate,
+            payload_size: m.get_one::<usize>("payload-size").cloned().unwrap(),
+            payload_seed: m.get_one::<u64>("payload-seed").cloned(),
+            accounts: m.get_one::<usize>("accounts").cloned().unwrap(),
         }
     }
 }
@@ -80,6 +201,59 @@ pub fn cli() -> Command {
                 .default_value("localhost:16210")
                 .help("RPC server"),
         )
+        .arg(
+            Arg::new("fee-rate")
+                .long("fee-rate")
+                .value_name("fee-rate")
+                .default_value("10")
+                .value_parser(clap::value_parser!(u64))
+                .help("Base fee rate in sompi per gram of mass"),
+        )
+        .arg(
+            Arg::new("max-fee-rate")
+                .long("max-fee-rate")
+                .value_name("max-fee-rate")
+                .default_value("100")
+                .value_parser(clap::value_parser!(u64))
+                .help("Ceiling fee rate the adaptive controller can escalate to under mempool pressure"),
+        )
+        .arg(
+            Arg::new("payload-size")
+                .long("payload-size")
+                .value_name("bytes")
+                .default_value("0")
+                .value_parser(clap::value_parser!(usize))
+                .help("Fill each generated transaction's payload with this many random bytes"),
+        )
+        .arg(
+            Arg::new("payload-seed")
+                .long("payload-seed")
+                .value_name("seed")
+                .value_parser(clap::value_parser!(u64))
+                .help("Seed the payload RNG for reproducible runs (default: seeded from entropy)"),
+        )
+        .arg(
+            Arg::new("accounts")
+                .long("accounts")
+                .value_name("n")
+                .default_value("1")
+                .value_parser(clap::value_parser!(usize))
+                .help(
+                    "Fan out submission across this many sub-accounts derived from the master key, \
+each with its own UTXO set and pending map, to escape single-account contention at high --tps",
+                ),
+        )
+        .subcommand(
+            Command::new("sweep")
+                .about("Drain every spendable UTXO owned by the key into as few transactions as possible, then exit")
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("to")
+                        .required(true)
+                        .help("Destination address to sweep all funds to"),
+                ),
+        )
 }
 
 #[tokio::main]
@@ -87,7 +261,7 @@ async fn main() {
     kaspa_core::log::init_logger(None, "");
     let args = Args::parse();
     let rpc_client = GrpcClient::connect(
-        NotificationMode::Direct,
+        NotificationMode::MultiListeners,
         format!("grpc://{}", args.rpc_server),
         true,
         None,
@@ -98,7 +272,6 @@ async fn main() {
     .await
     .unwrap();
     info!("Connected to RPC");
-    let pending = Arc::new(RwLock::new(HashMap::new()));
 
     let schnorr_key = if let Some(private_key_hex) = args.private_key {
         let mut private_key_bytes = [0u8; 32];
@@ -145,28 +318,220 @@ async fn main() {
         coinbase_maturity,
     );
 
-    let (submit_tx_send, submit_tx_recv) = mpsc::channel(100);
+    if let Some(sweep_to) = args.sweep_to {
+        sweep(&rpc_client, schnorr_key, kaspa_addr, sweep_to, coinbase_maturity, args.fee_rate).await;
+        return;
+    }
+
+    let account_keys = if args.accounts > 1 { derive_account_keys(&schnorr_key, args.accounts) } else { vec![schnorr_key] };
+    let account_addrs = account_keys
+        .iter()
+        .map(|key| Address::new(kaspa_addresses::Prefix::Testnet, kaspa_addresses::Version::PubKey, &key.x_only_public_key().0.serialize()))
+        .collect_vec();
+
+    if account_keys.len() > 1 {
+        fund_accounts(&rpc_client, schnorr_key, kaspa_addr, &account_addrs, coinbase_maturity, args.fee_rate).await;
+        wait_for_account_funding(&rpc_client, &account_addrs, coinbase_maturity).await;
+    }
 
-    let mut utxos = refresh_utxos(&rpc_client, kaspa_addr.clone(), pending.clone(), coinbase_maturity).await;
-    let utxos_len = Arc::new(AtomicUsize::new(utxos.len()));
+    let pending_submits = Arc::new(RwLock::new(HashMap::<TransactionId, u64>::new()));
+    let histogram = Arc::new(LatencyHistogram::new());
+    let fee_rate = Arc::new(AtomicU64::new(args.fee_rate));
+    let stats = Arc::new(Stats::new());
 
     {
         let rpc_client = rpc_client.clone();
-        let pending = pending.clone();
-        let utxos_len = utxos_len.clone();
-        tokio::spawn(async move { submit_loop(submit_tx_recv, schnorr_key, rpc_client, pending, utxos_len).await });
+        let pending_submits = pending_submits.clone();
+        let histogram = histogram.clone();
+        tokio::spawn(async move { track_confirmations(rpc_client, pending_submits, histogram).await });
     }
 
     let mut ticker = interval(Duration::from_secs_f64(1.0 / (args.tps as f64)));
     ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let rate_limiter = Arc::new(AsyncMutex::new(ticker));
+
+    let mut report_handles = Vec::with_capacity(account_keys.len());
+    let mut account_tasks = Vec::with_capacity(account_keys.len());
+    for (account_key, account_addr) in account_keys.into_iter().zip(account_addrs) {
+        let pending = Arc::new(RwLock::new(HashMap::new()));
+        let utxos = refresh_utxos(&rpc_client, account_addr.clone(), pending.clone(), coinbase_maturity).await;
+        let utxos_len = Arc::new(AtomicUsize::new(utxos.len()));
+        let utxos = Arc::new(RwLock::new(utxos));
+        report_handles.push((utxos_len.clone(), pending.clone()));
+
+        let rpc_client = rpc_client.clone();
+        let pending_submits = pending_submits.clone();
+        let fee_rate = fee_rate.clone();
+        let stats = stats.clone();
+        let rate_limiter = rate_limiter.clone();
+        account_tasks.push(tokio::spawn(async move {
+            run_account(
+                rpc_client,
+                account_key,
+                account_addr,
+                utxos,
+                pending,
+                utxos_len,
+                coinbase_maturity,
+                rate_limiter,
+                fee_rate,
+                args.fee_rate,
+                args.max_fee_rate,
+                args.payload_size,
+                args.payload_seed,
+                pending_submits,
+                stats,
+            )
+            .await
+        }));
+    }
+
+    tokio::spawn(report_loop(stats, histogram, fee_rate, report_handles));
+
+    for task in account_tasks {
+        task.await.unwrap();
+    }
+}
+
+/// Deterministically derives `n` schnorr keypairs from the master key so a multi-account run can
+/// be reproduced (and funds recovered) from the master private key alone.
+fn derive_account_keys(master_key: &KeyPair, n: usize) -> Vec<KeyPair> {
+    let master_sk_bytes = master_key.secret_bytes();
+    (0..n)
+        .map(|i| {
+            let mut hasher = Sha256::new();
+            hasher.update(b"rothschild-account-key");
+            hasher.update(master_sk_bytes);
+            hasher.update((i as u64).to_le_bytes());
+            let sk = secp256k1::SecretKey::from_slice(&hasher.finalize()).expect("derived scalar is a valid secp256k1 secret key");
+            KeyPair::from_secret_key(secp256k1::SECP256K1, &sk)
+        })
+        .collect()
+}
+
+/// Selects enough of the master key's spendable UTXOs to cover `ACCOUNT_FUNDING_AMOUNT` per
+/// sub-account plus the fee, and splits that amount evenly across `account_addrs` in a single
+/// funding transaction so each sub-account can run its own independent submission pipeline. The
+/// rest of the master key's balance stays with the master key.
+async fn fund_accounts(
+    rpc_client: &GrpcClient,
+    master_key: KeyPair,
+    master_addr: Address,
+    account_addrs: &[Address],
+    coinbase_maturity: u64,
+    fee_rate: u64,
+) {
+    info!("Funding {} sub-accounts from the master key", account_addrs.len());
+    let master_utxos = fetch_spendable_utxos(rpc_client, master_addr, coinbase_maturity).await;
+    let num_outs = account_addrs.len() as u64;
+    let min_amount = ACCOUNT_FUNDING_AMOUNT * num_outs;
+    let (selected_utxos, selected_amount) = select_utxos(&master_utxos, min_amount, num_outs, false, &HashMap::new(), 0, fee_rate);
+    if selected_amount == 0 {
+        panic!(
+            "Not enough funds in the master key to fund {} sub-accounts with {} sompi each",
+            account_addrs.len(),
+            ACCOUNT_FUNDING_AMOUNT
+        );
+    }
+
+    let amount_per_account = selected_amount / num_outs;
+    let tx = generate_funding_tx(&selected_utxos, amount_per_account, account_addrs);
+    let signed_tx =
+        sign(MutableTransaction::with_entries(tx, selected_utxos.iter().map(|(_, entry)| entry.clone()).collect_vec()), master_key);
+    rpc_client.submit_transaction((&signed_tx.tx).into(), false).await.expect("failed to submit the account funding tx");
+    info!("Submitted funding tx {}, sending {} sompi to each sub-account", signed_tx.tx.id(), amount_per_account);
+}
+
+/// Polls every sub-account's address until it holds at least one matured, spendable UTXO, so
+/// `run_account` starts with real funds on hand instead of hammering the node with refreshes on
+/// every tick while the funding tx clears the same maturity window `fetch_spendable_utxos` enforces.
+async fn wait_for_account_funding(rpc_client: &GrpcClient, account_addrs: &[Address], coinbase_maturity: u64) {
+    info!("Waiting for the funding tx to mature across {} sub-accounts", account_addrs.len());
+    const POLL_INTERVAL_SECS: u64 = 5;
+    loop {
+        let mut all_funded = true;
+        for account_addr in account_addrs {
+            if fetch_spendable_utxos(rpc_client, account_addr.clone(), coinbase_maturity).await.is_empty() {
+                all_funded = false;
+                break;
+            }
+        }
+        if all_funded {
+            return;
+        }
+        tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+    }
+}
+
+fn generate_funding_tx(utxos: &[(TransactionOutpoint, UtxoEntry)], amount_per_account: u64, addrs: &[Address]) -> Transaction {
+    let inputs = utxos
+        .iter()
+        .map(|(op, _)| TransactionInput { previous_outpoint: *op, signature_script: vec![], sequence: 0, sig_op_count: 1 })
+        .collect_vec();
+    let outputs =
+        addrs.iter().map(|addr| TransactionOutput { value: amount_per_account, script_public_key: pay_to_address_script(addr) }).collect_vec();
+    Transaction::new(TX_VERSION, inputs, outputs, 0, SUBNETWORK_ID_NATIVE, 0, vec![])
+}
+
+/// Runs one account's independent `maybe_send_tx`/`refresh_utxos`/`submit_loop` pipeline. Each
+/// account owns its own UTXO vector and pending map, so accounts never contend over them;
+/// `rate_limiter` is shared and enforces the global `--tps` across all of them. The adaptive
+/// `fee_rate`, the `stats` counters and the `pending_submits` latency map are shared as well.
+#[allow(clippy::too_many_arguments)]
+async fn run_account(
+    rpc_client: GrpcClient,
+    schnorr_key: KeyPair,
+    kaspa_addr: Address,
+    utxos: Arc<RwLock<Vec<(TransactionOutpoint, UtxoEntry)>>>,
+    pending: Arc<RwLock<HashMap<TransactionOutpoint, u64>>>,
+    utxos_len: Arc<AtomicUsize>,
+    coinbase_maturity: u64,
+    rate_limiter: Arc<AsyncMutex<Interval>>,
+    fee_rate: Arc<AtomicU64>,
+    base_fee_rate: u64,
+    max_fee_rate: u64,
+    payload_size: usize,
+    payload_seed: Option<u64>,
+    pending_submits: Arc<RwLock<HashMap<TransactionId, u64>>>,
+    stats: Arc<Stats>,
+) {
+    let (submit_tx_send, submit_tx_recv) = mpsc::channel(100);
+
+    {
+        let rpc_client = rpc_client.clone();
+        tokio::spawn(async move { submit_loop(submit_tx_recv, schnorr_key, rpc_client, pending_submits, stats).await });
+    }
+
+    {
+        let rpc_client = rpc_client.clone();
+        let kaspa_addr = kaspa_addr.clone();
+        let utxos = utxos.clone();
+        let pending = pending.clone();
+        let utxos_len = utxos_len.clone();
+        tokio::spawn(async move { track_utxo_changes(rpc_client, kaspa_addr, utxos, pending, utxos_len, coinbase_maturity).await });
+    }
 
     let mut maximize_inputs = false;
     let mut last_refresh = unix_now();
+    let mut payload_rng = match payload_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(thread_rng()).unwrap(),
+    };
     loop {
-        ticker.tick().await;
-        maximize_inputs = should_maximize_inputs(maximize_inputs, &utxos, &pending.read());
+        rate_limiter.lock().await.tick().await;
+        maximize_inputs = should_maximize_inputs(maximize_inputs, &utxos.read(), &pending.read());
         let now = unix_now();
-        let has_funds = match maybe_send_tx(kaspa_addr.clone(), &mut utxos, pending.clone(), maximize_inputs).await {
+        let has_funds = match maybe_send_tx(
+            kaspa_addr.clone(),
+            &utxos,
+            pending.clone(),
+            maximize_inputs,
+            fee_rate.load(Ordering::Relaxed),
+            payload_size,
+            &mut payload_rng,
+        )
+        .await
+        {
             Some(tx) => {
                 submit_tx_send.send(tx).await.unwrap();
                 true
@@ -179,15 +544,105 @@ async fn main() {
         if !has_funds || now - last_refresh > 60_000 {
             info!("Refetching UTXO set");
             tokio::time::sleep(Duration::from_millis(100)).await; // We don't want this operation to be too frequent since it's heavy on the node, so we wait some time before executing it.
-            utxos = refresh_utxos(&rpc_client, kaspa_addr.clone(), pending.clone(), coinbase_maturity).await;
-            utxos_len.store(utxos.len(), Ordering::Relaxed);
+            let refreshed = refresh_utxos(&rpc_client, kaspa_addr.clone(), pending.clone(), coinbase_maturity).await;
+            utxos_len.store(refreshed.len(), Ordering::Relaxed);
+            *utxos.write() = refreshed;
             last_refresh = unix_now();
-            pause_if_mempool_is_full(&rpc_client).await;
+            update_fee_rate_and_pause_if_mempool_is_full(&rpc_client, &fee_rate, base_fee_rate, max_fee_rate).await;
         }
         clean_old_pending_outpoints(&mut pending.write());
     }
 }
 
+/// Periodically aggregates every account's throughput into one combined report, alongside the
+/// shared confirmation-latency histogram and the current adaptive fee rate.
+async fn report_loop(
+    stats: Arc<Stats>,
+    histogram: Arc<LatencyHistogram>,
+    fee_rate: Arc<AtomicU64>,
+    accounts: Vec<(Arc<AtomicUsize>, Arc<RwLock<HashMap<TransactionOutpoint, u64>>>)>,
+) {
+    let mut ticker = interval(Duration::from_secs(50));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut since = unix_now();
+    loop {
+        ticker.tick().await;
+        let now = unix_now();
+        let time_past = now - since;
+        since = now;
+
+        let (num_txs, num_utxos, utxos_amount, num_outs) = stats.take();
+        if num_txs == 0 {
+            continue;
+        }
+
+        let utxos_len: usize = accounts.iter().map(|(len, _)| len.load(Ordering::SeqCst)).sum();
+        let pending_len: usize = accounts.iter().map(|(_, pending)| pending.read().len()).sum();
+        let (p50, p90, p99, timed_out) = histogram.take_percentiles();
+        info!(
+            "Tx rate: {:.1}/sec, avg UTXO amount: {}, avg UTXOs per tx: {}, avg outs per tx: {}, estimated available UTXOs: {}, \
+confirmation latency p50/p90/p99: {}/{}/{} ms, timed out: {}, fee rate: {} sompi/gram",
+            1000f64 * (num_txs as f64) / (time_past as f64),
+            (utxos_amount / num_utxos as u64),
+            num_utxos / num_txs,
+            num_outs / num_txs,
+            if utxos_len > pending_len { utxos_len - pending_len } else { 0 },
+            p50,
+            p90,
+            p99,
+            timed_out,
+            fee_rate.load(Ordering::Relaxed),
+        );
+    }
+}
+
+async fn sweep(rpc_client: &GrpcClient, schnorr_key: KeyPair, kaspa_addr: Address, to: String, coinbase_maturity: u64, fee_rate: u64) {
+    let to_addr = Address::try_from(to.as_str()).expect("invalid sweep destination address");
+    let utxos = fetch_spendable_utxos(rpc_client, kaspa_addr, coinbase_maturity).await;
+    if utxos.is_empty() {
+        info!("No spendable UTXOs found, nothing to sweep");
+        return;
+    }
+
+    let mut total_swept = 0u64;
+    let mut num_sweep_txs = 0usize;
+    for (selected_utxos, output_amount) in select_sweep_utxos(&utxos, fee_rate) {
+        if output_amount == 0 {
+            warn!("Skipping a batch of {} UTXOs: fee exceeds the batch's amount", selected_utxos.len());
+            continue;
+        }
+
+        let tx = generate_tx(&selected_utxos, output_amount, 1, &to_addr, vec![]);
+        let signed_tx = sign(
+            MutableTransaction::with_entries(tx, selected_utxos.iter().map(|(_, entry)| entry.clone()).collect_vec()),
+            schnorr_key,
+        );
+        match rpc_client.submit_transaction((&signed_tx.tx).into(), false).await {
+            Ok(_) => {
+                total_swept += output_amount;
+                num_sweep_txs += 1;
+            }
+            Err(e) => warn!("RPC error when submitting sweep tx {}: {}", signed_tx.tx.id(), e),
+        }
+    }
+
+    info!("Swept {} sompi to {} across {} transaction(s)", total_swept, String::from(&to_addr), num_sweep_txs);
+}
+
+/// Greedily batches every UTXO into groups of at most `MAX_UTXOS` inputs, each paid out as a
+/// single output, so a sweep drains the whole balance in as few transactions as possible.
+fn select_sweep_utxos(utxos: &[(TransactionOutpoint, UtxoEntry)], fee_rate: u64) -> Vec<(Vec<(TransactionOutpoint, UtxoEntry)>, u64)> {
+    utxos
+        .chunks(MAX_UTXOS)
+        .map(|chunk| {
+            let selected = chunk.to_vec();
+            let selected_amount = selected.iter().map(|(_, entry)| entry.amount).sum::<u64>();
+            let fee = required_fee(selected.len(), 1, 0, fee_rate);
+            (selected, selected_amount.saturating_sub(fee))
+        })
+        .collect()
+}
+
 struct TxToSign {
     tx: Transaction,
     utxos: Box<[(TransactionOutpoint, UtxoEntry)]>,
@@ -197,10 +652,9 @@ async fn submit_loop(
     mut submit_tx_recv: mpsc::Receiver<TxToSign>,
     schnorr_key: KeyPair,
     rpc_client: GrpcClient,
-    pending: Arc<RwLock<HashMap<TransactionOutpoint, u64>>>,
-    utxos_len: Arc<AtomicUsize>,
+    pending_submits: Arc<RwLock<HashMap<TransactionId, u64>>>,
+    stats: Arc<Stats>,
 ) {
-    let mut stats = Stats { num_txs: 0, since: unix_now(), num_utxos: 0, utxos_amount: 0, num_outs: 0 };
     let num_cpus = num_cpus::get();
     loop {
         match submit_tx_recv.recv().await {
@@ -233,33 +687,114 @@ async fn submit_loop(
                             continue;
                         }
                     }
+                    pending_submits.write().insert(tx.id(), unix_now());
+                    stats.record(tx.inputs.len(), amount_used, tx.outputs.len());
+                }
+            }
+            None => return,
+        }
+    }
+}
 
-                    stats.num_txs += 1;
-                    stats.num_utxos += tx.inputs.len();
-                    stats.utxos_amount += amount_used;
-                    stats.num_outs += tx.outputs.len();
-                    let now = unix_now();
-                    let time_past = now - stats.since;
-                    if time_past > 50_000 {
-                        let pending_len = pending.read().len();
-                        let utxos_len = utxos_len.load(Ordering::SeqCst);
-                        info!(
-                            "Tx rate: {:.1}/sec, avg UTXO amount: {}, avg UTXOs per tx: {}, avg outs per tx: {}, estimated available UTXOs: {}",
-                            1000f64 * (stats.num_txs as f64) / (time_past as f64),
-                            (stats.utxos_amount / stats.num_utxos as u64),
-                            stats.num_utxos / stats.num_txs,
-                            stats.num_outs / stats.num_txs,
-                            if utxos_len > pending_len { utxos_len - pending_len } else { 0 },
-                        );
-                        stats.since = now;
-                        stats.num_txs = 0;
-                        stats.num_utxos = 0;
-                        stats.utxos_amount = 0;
-                        stats.num_outs = 0;
+/// Listens for virtual-chain-changed notifications, matches accepted tx ids
+/// against `pending_submits`, and feeds the elapsed time into `histogram`. Txs that linger in
+/// `pending_submits` past `LATENCY_TIMEOUT_MS` are assumed dropped and counted as timeouts.
+async fn track_confirmations(
+    rpc_client: GrpcClient,
+    pending_submits: Arc<RwLock<HashMap<TransactionId, u64>>>,
+    histogram: Arc<LatencyHistogram>,
+) {
+    let channel = Channel::unbounded();
+    let listener_id = rpc_client.register_new_listener(ChannelConnection::new(channel.sender.clone()));
+    rpc_client
+        .start_notify(listener_id, Scope::VirtualChainChanged(VirtualChainChangedScope { include_accepted_transaction_ids: true }))
+        .await
+        .unwrap();
+
+    let mut cleanup_ticker = interval(Duration::from_secs(30));
+    loop {
+        tokio::select! {
+            notification = channel.receiver.recv() => {
+                let Ok(notification) = notification else { return };
+                // Only the virtual chain actually merging a tx into the accepted chain counts as
+                // confirmation; a bare `BlockAdded` doesn't guarantee the block (or tx) ever gets
+                // accepted, so it must not be allowed to retire a pending entry on its own.
+                let accepted_ids: Vec<TransactionId> = match notification {
+                    Notification::VirtualChainChanged(notification) => {
+                        notification.accepted_transaction_ids.iter().flat_map(|accepted| accepted.accepted_transaction_ids.clone()).collect()
+                    }
+                    _ => continue,
+                };
+
+                if accepted_ids.is_empty() {
+                    continue;
+                }
+
+                let now = unix_now();
+                let mut pending_submits = pending_submits.write();
+                for tx_id in accepted_ids {
+                    if let Some(submit_time) = pending_submits.remove(&tx_id) {
+                        histogram.record(now.saturating_sub(submit_time));
                     }
                 }
             }
-            None => return,
+            _ = cleanup_ticker.tick() => {
+                let now = unix_now();
+                let mut pending_submits = pending_submits.write();
+                let timed_out_ids =
+                    pending_submits.iter().filter(|(_, submit_time)| now - *submit_time > LATENCY_TIMEOUT_MS).map(|(id, _)| *id).collect_vec();
+                for tx_id in timed_out_ids {
+                    pending_submits.remove(&tx_id);
+                    histogram.record_timeout();
+                }
+            }
+        }
+    }
+}
+
+/// Subscribes to UTXO-changed notifications for `kaspa_addr` and reconciles `utxos`/`pending` as
+/// outpoints are spent or created, instead of waiting for the periodic `refresh_utxos` fallback.
+async fn track_utxo_changes(
+    rpc_client: GrpcClient,
+    kaspa_addr: Address,
+    utxos: Arc<RwLock<Vec<(TransactionOutpoint, UtxoEntry)>>>,
+    pending: Arc<RwLock<HashMap<TransactionOutpoint, u64>>>,
+    utxos_len: Arc<AtomicUsize>,
+    coinbase_maturity: u64,
+) {
+    let channel = Channel::unbounded();
+    let listener_id = rpc_client.register_new_listener(ChannelConnection::new(channel.sender.clone()));
+    rpc_client.start_notify(listener_id, Scope::UtxosChanged(UtxosChangedScope { addresses: vec![kaspa_addr] })).await.unwrap();
+
+    while let Ok(notification) = channel.receiver.recv().await {
+        let Notification::UtxosChanged(notification) = notification else { continue };
+
+        let removed_outpoints = notification.removed.iter().map(|entry| entry.outpoint).collect_vec();
+        let mut added_utxos = notification.added.iter().map(|entry| (entry.outpoint, entry.utxo_entry.clone())).collect_vec();
+        if removed_outpoints.is_empty() && added_utxos.is_empty() {
+            continue;
+        }
+
+        if !added_utxos.is_empty() {
+            // Newly reported UTXOs aren't spendable until they clear the same confirmation depth
+            // `fetch_spendable_utxos` requires, so filter them the same way before adding them in.
+            let virtual_daa_score = rpc_client.get_block_dag_info().await.unwrap().virtual_daa_score;
+            added_utxos.retain(|(_, entry)| is_utxo_spendable(entry, virtual_daa_score, coinbase_maturity));
+        }
+
+        {
+            let mut utxos = utxos.write();
+            utxos.retain(|(outpoint, _)| !removed_outpoints.contains(outpoint));
+            utxos.extend(added_utxos);
+            utxos.sort_by(|a, b| b.1.amount.cmp(&a.1.amount));
+            utxos_len.store(utxos.len(), Ordering::Relaxed);
+        }
+
+        if !removed_outpoints.is_empty() {
+            let mut pending = pending.write();
+            for outpoint in removed_outpoints {
+                pending.remove(&outpoint);
+            }
         }
     }
 }
@@ -281,10 +816,19 @@ fn should_maximize_inputs(
     }
 }
 
-async fn pause_if_mempool_is_full(rpc_client: &GrpcClient) {
+/// Samples the live mempool occupancy, ramps `fee_rate` toward `max_fee_rate` as it rises above
+/// `MEMPOOL_LOW_WATER_MARK` and decays it back toward `base_fee_rate` as it drains, and still
+/// pauses submission outright once the mempool is fully saturated.
+async fn update_fee_rate_and_pause_if_mempool_is_full(
+    rpc_client: &GrpcClient,
+    fee_rate: &AtomicU64,
+    base_fee_rate: u64,
+    max_fee_rate: u64,
+) {
     loop {
         let mempool_size = rpc_client.get_info().await.unwrap().mempool_size;
-        if mempool_size < 10_000 {
+        fee_rate.store(compute_fee_rate(mempool_size, base_fee_rate, max_fee_rate), Ordering::Relaxed);
+        if mempool_size < MEMPOOL_HIGH_WATER_MARK {
             break;
         }
 
@@ -294,6 +838,20 @@ async fn pause_if_mempool_is_full(rpc_client: &GrpcClient) {
     }
 }
 
+/// Linearly ramps the fee rate from `base_fee_rate` to `max_fee_rate` as `mempool_size` moves from
+/// `MEMPOOL_LOW_WATER_MARK` to `MEMPOOL_HIGH_WATER_MARK`.
+fn compute_fee_rate(mempool_size: u64, base_fee_rate: u64, max_fee_rate: u64) -> u64 {
+    if mempool_size <= MEMPOOL_LOW_WATER_MARK {
+        return base_fee_rate;
+    }
+    if mempool_size >= MEMPOOL_HIGH_WATER_MARK {
+        return max_fee_rate;
+    }
+
+    let ramp = (mempool_size - MEMPOOL_LOW_WATER_MARK) as f64 / (MEMPOOL_HIGH_WATER_MARK - MEMPOOL_LOW_WATER_MARK) as f64;
+    base_fee_rate + ((max_fee_rate - base_fee_rate) as f64 * ramp) as u64
+}
+
 async fn refresh_utxos(
     rpc_client: &GrpcClient,
     kaspa_addr: Address,
@@ -349,17 +907,22 @@ fn is_utxo_spendable(entry: &UtxoEntry, virtual_daa_score: u64, coinbase_maturit
 
 async fn maybe_send_tx(
     kaspa_addr: Address,
-    utxos: &mut Vec<(TransactionOutpoint, UtxoEntry)>,
+    utxos: &RwLock<Vec<(TransactionOutpoint, UtxoEntry)>>,
     pending: Arc<RwLock<HashMap<TransactionOutpoint, u64>>>,
     maximize_inputs: bool,
+    fee_rate: u64,
+    payload_size: usize,
+    payload_rng: &mut StdRng,
 ) -> Option<TxToSign> {
     let num_outs = if maximize_inputs { 1 } else { 2 };
-    let (selected_utxos, selected_amount) = select_utxos(utxos, DEFAULT_SEND_AMOUNT, num_outs, maximize_inputs, &pending.read());
+    let (selected_utxos, selected_amount) =
+        select_utxos(&utxos.read(), DEFAULT_SEND_AMOUNT, num_outs, maximize_inputs, &pending.read(), payload_size as u64, fee_rate);
     if selected_amount == 0 {
         return None;
     }
 
-    let tx = generate_tx(&selected_utxos, selected_amount, num_outs, &kaspa_addr);
+    let payload = random_payload(payload_size, payload_rng);
+    let tx = generate_tx(&selected_utxos, selected_amount, num_outs, &kaspa_addr, payload);
 
     let now = unix_now();
     {
@@ -380,15 +943,15 @@ fn clean_old_pending_outpoints(pending: &mut HashMap<TransactionOutpoint, u64>)
     }
 }
 
-fn required_fee(num_utxos: usize, num_outs: u64) -> u64 {
-    FEE_PER_MASS * estimated_mass(num_utxos, num_outs)
+fn required_fee(num_utxos: usize, num_outs: u64, payload_len: u64, fee_rate: u64) -> u64 {
+    fee_rate * estimated_mass(num_utxos, num_outs, payload_len)
 }
 
-fn estimated_mass(num_utxos: usize, num_outs: u64) -> u64 {
-    200 + 34 * num_outs + 1000 * (num_utxos as u64)
+fn estimated_mass(num_utxos: usize, num_outs: u64, payload_len: u64) -> u64 {
+    200 + 34 * num_outs + 1000 * (num_utxos as u64) + payload_len
 }
 
-fn generate_tx(utxos: &[(TransactionOutpoint, UtxoEntry)], send_amount: u64, num_outs: u64, kaspa_addr: &Address) -> Transaction {
+fn generate_tx(utxos: &[(TransactionOutpoint, UtxoEntry)], send_amount: u64, num_outs: u64, kaspa_addr: &Address, payload: Vec<u8>) -> Transaction {
     let script_public_key = pay_to_address_script(kaspa_addr);
     let inputs = utxos
         .iter()
@@ -398,25 +961,34 @@ fn generate_tx(utxos: &[(TransactionOutpoint, UtxoEntry)], send_amount: u64, num
     let outputs = (0..num_outs)
         .map(|_| TransactionOutput { value: send_amount / num_outs, script_public_key: script_public_key.clone() })
         .collect_vec();
-    let unsigned_tx = Transaction::new(TX_VERSION, inputs, outputs, 0, SUBNETWORK_ID_NATIVE, 0, vec![]);
+    let unsigned_tx = Transaction::new(TX_VERSION, inputs, outputs, 0, SUBNETWORK_ID_NATIVE, 0, payload);
     unsigned_tx
 }
 
+/// Fills a payload of `payload_size` cryptographically random bytes, or an empty payload when
+/// `payload_size` is zero (the default).
+fn random_payload(payload_size: usize, rng: &mut StdRng) -> Vec<u8> {
+    let mut payload = vec![0u8; payload_size];
+    rng.fill_bytes(&mut payload);
+    payload
+}
+
 fn select_utxos(
     utxos: &[(TransactionOutpoint, UtxoEntry)],
     min_amount: u64,
     num_outs: u64,
     maximize_utxos: bool,
     pending: &HashMap<TransactionOutpoint, u64>,
+    payload_len: u64,
+    fee_rate: u64,
 ) -> (Vec<(TransactionOutpoint, UtxoEntry)>, u64) {
-    const MAX_UTXOS: usize = 84;
     let mut selected_amount: u64 = 0;
     let mut selected = Vec::new();
     for (outpoint, entry) in utxos.iter().filter(|(op, _)| !pending.contains_key(op)).cloned() {
         selected_amount += entry.amount;
         selected.push((outpoint, entry));
 
-        let fee = required_fee(selected.len(), num_outs);
+        let fee = required_fee(selected.len(), num_outs, payload_len, fee_rate);
 
         if selected_amount >= min_amount + fee && (!maximize_utxos || selected.len() == MAX_UTXOS) {
             return (selected, selected_amount - fee);
@@ -429,3 +1001,97 @@ fn select_utxos(
 
     (vec![], 0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaspa_consensus_core::tx::ScriptPublicKey;
+
+    fn test_utxo(index: u32, amount: u64) -> (TransactionOutpoint, UtxoEntry) {
+        (TransactionOutpoint::new(TransactionId::from_bytes([0; 32]), index), UtxoEntry::new(amount, ScriptPublicKey::default(), 0, false))
+    }
+
+    #[test]
+    fn select_sweep_utxos_splits_into_max_utxos_batches() {
+        let utxos: Vec<_> = (0..(MAX_UTXOS + 1) as u32).map(|i| test_utxo(i, 1_000_000)).collect();
+        let batches = select_sweep_utxos(&utxos, 1);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0.len(), MAX_UTXOS);
+        assert_eq!(batches[1].0.len(), 1);
+    }
+
+    #[test]
+    fn select_sweep_utxos_subtracts_the_required_fee() {
+        let utxos = vec![test_utxo(0, 1_000_000)];
+        let fee_rate = 10;
+        let (batch, output_amount) = &select_sweep_utxos(&utxos, fee_rate)[0];
+        let expected_fee = required_fee(batch.len(), 1, 0, fee_rate);
+        assert_eq!(*output_amount, 1_000_000 - expected_fee);
+    }
+
+    #[test]
+    fn select_sweep_utxos_floors_dust_batches_to_zero() {
+        let utxos = vec![test_utxo(0, 1)];
+        let (_, output_amount) = &select_sweep_utxos(&utxos, 1_000_000)[0];
+        assert_eq!(*output_amount, 0);
+    }
+
+    #[test]
+    fn histogram_derives_percentiles_from_bucketed_samples() {
+        let histogram = LatencyHistogram::new();
+        histogram.record(1); // bucket 0: [1, 2) ms
+        histogram.record(1000); // bucket 9: [512, 1024) ms
+        let (p50, p90, p99, timed_out) = histogram.take_percentiles();
+        assert_eq!((p50, p90, p99, timed_out), (1, 512, 512, 0));
+    }
+
+    #[test]
+    fn histogram_clamps_zero_latency_into_the_first_bucket() {
+        let histogram = LatencyHistogram::new();
+        histogram.record(0);
+        let (p50, p90, p99, _) = histogram.take_percentiles();
+        assert_eq!((p50, p90, p99), (1, 1, 1));
+    }
+
+    #[test]
+    fn histogram_counts_timeouts_separately_from_samples() {
+        let histogram = LatencyHistogram::new();
+        histogram.record_timeout();
+        histogram.record_timeout();
+        let (p50, p90, p99, timed_out) = histogram.take_percentiles();
+        assert_eq!((p50, p90, p99), (0, 0, 0));
+        assert_eq!(timed_out, 2);
+    }
+
+    #[test]
+    fn histogram_take_percentiles_drains_the_window() {
+        let histogram = LatencyHistogram::new();
+        histogram.record(100);
+        histogram.take_percentiles();
+        assert_eq!(histogram.take_percentiles(), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn compute_fee_rate_uses_base_rate_at_or_below_low_water_mark() {
+        assert_eq!(compute_fee_rate(0, 10, 100), 10);
+        assert_eq!(compute_fee_rate(MEMPOOL_LOW_WATER_MARK, 10, 100), 10);
+    }
+
+    #[test]
+    fn compute_fee_rate_uses_max_rate_at_or_above_high_water_mark() {
+        assert_eq!(compute_fee_rate(MEMPOOL_HIGH_WATER_MARK, 10, 100), 100);
+        assert_eq!(compute_fee_rate(MEMPOOL_HIGH_WATER_MARK + 1_000, 10, 100), 100);
+    }
+
+    #[test]
+    fn compute_fee_rate_ramps_linearly_between_water_marks() {
+        let midpoint = (MEMPOOL_LOW_WATER_MARK + MEMPOOL_HIGH_WATER_MARK) / 2;
+        assert_eq!(compute_fee_rate(midpoint, 10, 100), 55);
+    }
+
+    #[test]
+    fn compute_fee_rate_is_a_no_op_ramp_when_rates_are_equal() {
+        let midpoint = (MEMPOOL_LOW_WATER_MARK + MEMPOOL_HIGH_WATER_MARK) / 2;
+        assert_eq!(compute_fee_rate(midpoint, 10, 10), 10);
+    }
+}